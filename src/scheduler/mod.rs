@@ -3,9 +3,14 @@ use std::{fmt::Debug, collections::HashSet};
 
 mod data;
 mod dfs;
+mod dpor;
+mod icb;
+mod non_preemptive;
+mod parallel;
 mod pct;
 mod random;
 mod replay;
+mod rng;
 mod round_robin;
 mod determinism_check;
 
@@ -15,12 +20,72 @@ pub(crate) mod serialization;
 pub use crate::runtime::task::TaskId;
 
 pub use dfs::DfsScheduler;
+pub use dpor::DporScheduler;
+pub use icb::IterativeContextBoundingScheduler;
+pub use non_preemptive::NonPreemptiveScheduler;
+pub use parallel::ParallelScheduler;
 pub use pct::PctScheduler;
 pub use random::RandomScheduler;
 pub use replay::ReplayScheduler;
 pub use round_robin::RoundRobinScheduler;
 pub use determinism_check::DeterminismCheckScheduler;
 
+/// An opaque identifier for a synchronization object (a mutex, channel, atomic, ...). Operations on
+/// different objects are independent, so lookahead-aware schedulers use this to decide whether two
+/// operations could race.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ObjectId(pub usize);
+
+/// Describes the next operation a runnable task is about to perform, as seen by the scheduler at a
+/// decision point.
+///
+/// The executor annotates each runnable task with its `Lookahead` so that schedulers can make
+/// smarter choices: for example, [`PctScheduler`](crate::scheduler::PctScheduler) can avoid
+/// spending a priority change on a step that is about to block, and custom schedulers can detect
+/// operations on independent objects. This follows dejafu's scheduler interface, where the runnable
+/// set is annotated with per-thread lookahead.
+///
+/// Object-touching operations carry the [`ObjectId`] they act on, so a scheduler can tell whether
+/// two operations contend for the same object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Lookahead {
+    /// The task is about to acquire the given mutex (or other lock).
+    MutexLock(ObjectId),
+    /// The task is about to read from the given shared location.
+    Read(ObjectId),
+    /// The task is about to write to the given shared location.
+    Write(ObjectId),
+    /// The task is about to receive from the given channel.
+    ChannelRecv(ObjectId),
+    /// The task is about to spawn a new task.
+    Spawn,
+    /// The task is about to yield.
+    Yield,
+    /// The task is about to exit.
+    Exit,
+    /// The next operation is not one the executor classifies, or is not known.
+    Unknown,
+}
+
+impl Lookahead {
+    /// The synchronization object this operation acts on, if any. Operations that touch no object
+    /// (spawn, yield, exit) and unclassified operations return `None`.
+    pub fn object(&self) -> Option<ObjectId> {
+        match self {
+            Lookahead::MutexLock(id) | Lookahead::Read(id) | Lookahead::Write(id) | Lookahead::ChannelRecv(id) => {
+                Some(*id)
+            }
+            Lookahead::Spawn | Lookahead::Yield | Lookahead::Exit | Lookahead::Unknown => None,
+        }
+    }
+
+    /// Whether this operation mutates the object it touches (a lock acquire, channel receive, or
+    /// write), as opposed to a read.
+    pub fn is_mutation(&self) -> bool {
+        matches!(self, Lookahead::MutexLock(_) | Lookahead::Write(_) | Lookahead::ChannelRecv(_))
+    }
+}
+
 /// A `Schedule` determines the order in which tasks are to be executed
 // TODO would be nice to make this generic in the type of `seed`, but for now all our seeds are u64s
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -51,9 +116,23 @@ impl ScheduleRecord {
 		for task_id in options_vec {
 			new_record.runnable_tasks.insert(task_id.clone());
 		}
-		
+
 		new_record
     }
+
+    /// The set of tasks that were runnable at this decision point.
+    pub fn runnable_tasks(&self) -> &HashSet<TaskId> {
+        &self.runnable_tasks
+    }
+
+    /// The task that was chosen to run at this decision point, or `None` if the step was a random
+    /// choice rather than a task.
+    pub fn chosen_task(&self) -> Option<TaskId> {
+        match &self.step {
+            ScheduleStep::Task(task_id) => Some(task_id.clone()),
+            ScheduleStep::Random => None,
+        }
+    }
 }
 
 impl Schedule {
@@ -126,6 +205,41 @@ pub trait Scheduler: Debug {
         is_yielding: bool,
     ) -> Option<TaskId>;
 
+    /// Like [`next_task`](Scheduler::next_task), but also given `lookahead[i]`, the next operation
+    /// that `runnable_tasks[i]` is about to perform. The executor calls this method at every
+    /// scheduling decision; the default implementation ignores the lookahead and defers to
+    /// `next_task`, so existing schedulers need no changes. Schedulers that want to exploit
+    /// lookahead (e.g. to avoid spending a choice on a step that is about to block, or to detect
+    /// independent operations) override this method instead.
+    fn next_task_with_lookahead(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        _lookahead: &[Lookahead],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.next_task(runnable_tasks, current_task, is_yielding)
+    }
+
+    /// Like [`next_task_with_lookahead`](Scheduler::next_task_with_lookahead), but also given
+    /// `trace`, the accumulated sequence of scheduling decisions made so far in the current
+    /// execution (most recent last). Each [`ScheduleRecord`] records the step that was chosen along
+    /// with the set of tasks that were runnable at that point, so history-dependent schedulers can
+    /// inspect which alternatives were available and taken at earlier steps without maintaining
+    /// their own shadow copy of the trace. The executor calls this method at every scheduling
+    /// decision; the default implementation ignores the trace and defers to
+    /// `next_task_with_lookahead`, so schedulers that do not need history need no changes.
+    fn next_task_with_context(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        lookahead: &[Lookahead],
+        _trace: &[ScheduleRecord],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.next_task_with_lookahead(runnable_tasks, lookahead, current_task, is_yielding)
+    }
+
     /// Choose the next u64 value to return to the currently running task.
     fn next_u64(&mut self) -> u64;
 }
@@ -144,6 +258,28 @@ impl Scheduler for Box<dyn Scheduler + Send> {
         self.as_mut().next_task(runnable_tasks, current_task, is_yielding)
     }
 
+    fn next_task_with_lookahead(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        lookahead: &[Lookahead],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.as_mut().next_task_with_lookahead(runnable_tasks, lookahead, current_task, is_yielding)
+    }
+
+    fn next_task_with_context(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        lookahead: &[Lookahead],
+        trace: &[ScheduleRecord],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.as_mut()
+            .next_task_with_context(runnable_tasks, lookahead, trace, current_task, is_yielding)
+    }
+
     fn next_u64(&mut self) -> u64 {
         self.as_mut().next_u64()
     }
@@ -0,0 +1,133 @@
+//! A scheduler combinator that turns any scheduler into a non-preemptive one.
+use crate::scheduler::{Lookahead, Schedule, Scheduler, TaskId};
+
+/// A `NonPreemptiveScheduler` wraps an existing [`Scheduler`] so that it never performs a context
+/// switch while the currently running task is still able to make progress.
+///
+/// Concretely, as long as `current_task` remains runnable and has not asked to yield, this
+/// scheduler keeps running it and only consults the inner scheduler when the current task blocks,
+/// exits, or yields. This mirrors dejafu's `makeNonPreemptive` combinator (e.g. `roundRobinSchedNP`)
+/// and lets users cheaply explore the much smaller space of interleavings where context switches
+/// only happen at blocking points, which is a quick way to triage whether a bug requires
+/// preemption at all.
+#[derive(Debug)]
+pub struct NonPreemptiveScheduler<S: Scheduler> {
+    inner: S,
+}
+
+impl<S: Scheduler> NonPreemptiveScheduler<S> {
+    /// Create a new `NonPreemptiveScheduler` wrapping the given scheduler.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// The current task, if it should keep running rather than yield to another task; `None` if the
+    /// inner scheduler should choose.
+    fn keep_running(current_task: Option<TaskId>, runnable_tasks: &[TaskId], is_yielding: bool) -> Option<TaskId> {
+        match current_task {
+            Some(current) if !is_yielding && runnable_tasks.contains(&current) => Some(current),
+            _ => None,
+        }
+    }
+}
+
+impl<S: Scheduler> Scheduler for NonPreemptiveScheduler<S> {
+    fn new_execution(&mut self) -> Option<Schedule> {
+        self.inner.new_execution()
+    }
+
+    fn next_task(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        Self::keep_running(current_task, runnable_tasks, is_yielding)
+            .or_else(|| self.inner.next_task(runnable_tasks, current_task, is_yielding))
+    }
+
+    fn next_task_with_lookahead(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        lookahead: &[Lookahead],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        Self::keep_running(current_task, runnable_tasks, is_yielding).or_else(|| {
+            self.inner.next_task_with_lookahead(runnable_tasks, lookahead, current_task, is_yielding)
+        })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tid(id: usize) -> TaskId {
+        TaskId::from(id)
+    }
+
+    /// An inner scheduler that always picks the *last* runnable task, so its choice is
+    /// distinguishable from simply keeping the current task running.
+    #[derive(Debug)]
+    struct LastRunnable;
+
+    impl Scheduler for LastRunnable {
+        fn new_execution(&mut self) -> Option<Schedule> {
+            Some(Schedule::new(7))
+        }
+
+        fn next_task(
+            &mut self,
+            runnable_tasks: &[TaskId],
+            _current_task: Option<TaskId>,
+            _is_yielding: bool,
+        ) -> Option<TaskId> {
+            runnable_tasks.last().cloned()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            42
+        }
+    }
+
+    #[test]
+    fn keeps_current_task_while_runnable() {
+        let mut scheduler = NonPreemptiveScheduler::new(LastRunnable);
+        let runnable = vec![tid(0), tid(1)];
+        assert_eq!(scheduler.next_task(&runnable, Some(tid(0)), false), Some(tid(0)));
+        assert_eq!(scheduler.next_task_with_lookahead(&runnable, &[], Some(tid(0)), false), Some(tid(0)));
+    }
+
+    #[test]
+    fn delegates_when_current_task_is_yielding() {
+        let mut scheduler = NonPreemptiveScheduler::new(LastRunnable);
+        let runnable = vec![tid(0), tid(1)];
+        assert_eq!(scheduler.next_task(&runnable, Some(tid(0)), true), Some(tid(1)));
+    }
+
+    #[test]
+    fn delegates_when_current_task_is_not_runnable() {
+        let mut scheduler = NonPreemptiveScheduler::new(LastRunnable);
+        let runnable = vec![tid(0), tid(1)];
+        assert_eq!(scheduler.next_task(&runnable, Some(tid(2)), false), Some(tid(1)));
+    }
+
+    #[test]
+    fn delegates_before_the_execution_begins() {
+        let mut scheduler = NonPreemptiveScheduler::new(LastRunnable);
+        let runnable = vec![tid(0), tid(1)];
+        assert_eq!(scheduler.next_task(&runnable, None, false), Some(tid(1)));
+    }
+
+    #[test]
+    fn forwards_new_execution_and_next_u64_to_inner() {
+        let mut scheduler = NonPreemptiveScheduler::new(LastRunnable);
+        assert!(scheduler.new_execution().is_some());
+        assert_eq!(scheduler.next_u64(), 42);
+    }
+}
@@ -0,0 +1,498 @@
+//! Parallel schedule exploration using a shared frontier of per-worker queues.
+use crate::scheduler::{Lookahead, Schedule, ScheduleRecord, ScheduleStep, Scheduler, TaskId};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The owning end of a double-ended work queue. The owner pushes and pops from the back (LIFO),
+/// which keeps recently discovered branch points hot in cache, while other workers steal from the
+/// front.
+///
+/// This is a straightforward mutex-backed [`VecDeque`], not a lock-free work-stealing deque: it
+/// gives the same one-owner / many-stealers ownership split, which is all the explorer needs, but
+/// every access takes the lock. A lock-free deque would reduce contention under many workers.
+#[derive(Debug)]
+pub struct Worker<T> {
+    inner: Arc<Mutex<VecDeque<T>>>,
+}
+
+/// A handle that can steal work from a [`Worker`]'s queue. Cheap to clone and share between threads.
+#[derive(Debug, Clone)]
+pub struct Stealer<T> {
+    inner: Arc<Mutex<VecDeque<T>>>,
+}
+
+impl<T> Worker<T> {
+    /// Create an empty deque.
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Obtain a [`Stealer`] that shares this deque.
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+
+    /// Push a new item onto the back of the deque.
+    pub fn push(&self, item: T) {
+        self.inner.lock().unwrap().push_back(item);
+    }
+
+    /// Pop the most recently pushed item off the back of the deque.
+    pub fn pop(&self) -> Option<T> {
+        self.inner.lock().unwrap().pop_back()
+    }
+
+    /// Return true if the deque is currently empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().unwrap().is_empty()
+    }
+}
+
+impl<T> Default for Worker<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Stealer<T> {
+    /// Steal the oldest item from the front of the victim's deque.
+    pub fn steal(&self) -> Option<T> {
+        self.inner.lock().unwrap().pop_front()
+    }
+}
+
+/// The result of running one schedule prefix to completion.
+#[derive(Debug, Default)]
+pub struct Exploration {
+    /// Newly discovered branch prefixes (serializable [`Schedule`]s) to enqueue for later
+    /// exploration.
+    pub branches: Vec<Schedule>,
+    /// Set if this schedule exhibited a failure.
+    pub failure: Option<Schedule>,
+}
+
+/// Shared termination and result state, observed by every worker thread.
+#[derive(Debug)]
+struct Shared {
+    /// The amount of work that still has to be accounted for: pending prefixes in the frontier plus
+    /// prefixes currently in flight. Seeded to `1` for the root, incremented by the number of
+    /// branches a prefix produces and decremented once that prefix finishes. Exploration is complete
+    /// — globally quiescent — exactly when this reaches zero, which cannot happen spuriously at
+    /// startup because the root is counted before any worker spins up.
+    outstanding: AtomicUsize,
+    /// The number of executions started so far, to honour a `max_executions` bound.
+    executions: AtomicUsize,
+    /// Set once any worker records a failing schedule.
+    failed: AtomicBool,
+    /// The first failing schedule discovered, for deterministic single-threaded replay.
+    failure: Mutex<Option<Schedule>>,
+}
+
+/// A driver that spreads independent schedules across a pool of worker threads using a shared
+/// frontier of [`Worker`] queues.
+///
+/// Each worker owns a deque of pending schedule prefixes. A worker runs a prefix to completion with
+/// the supplied closure, pushes any newly discovered branch prefixes onto its own deque, and steals
+/// from its peers when it runs dry. Exploration stops when the frontier is globally quiescent (no
+/// pending prefixes and no busy worker), when the optional execution bound is reached, or — if
+/// configured — as soon as the first failing schedule is found. That first failure's [`Schedule`]
+/// is returned so it can be replayed deterministically in a single thread with a
+/// [`ReplayScheduler`](crate::scheduler::ReplayScheduler).
+#[derive(Debug, Clone)]
+pub struct ParallelExplorer {
+    num_workers: usize,
+    stop_on_first_failure: bool,
+    max_executions: Option<usize>,
+}
+
+impl ParallelExplorer {
+    /// Create a driver that uses `num_workers` worker threads (at least one).
+    pub fn new(num_workers: usize) -> Self {
+        Self {
+            num_workers: num_workers.max(1),
+            stop_on_first_failure: true,
+            max_executions: None,
+        }
+    }
+
+    /// Set whether exploration should stop as soon as the first failing schedule is found.
+    pub fn stop_on_first_failure(mut self, stop: bool) -> Self {
+        self.stop_on_first_failure = stop;
+        self
+    }
+
+    /// Bound the total number of executions across all workers (`None` for unbounded).
+    pub fn max_executions(mut self, max: Option<usize>) -> Self {
+        self.max_executions = max;
+        self
+    }
+
+    /// Explore the tree of schedules rooted at `root`, running each prefix with `run`. Returns the
+    /// first failing schedule discovered, if any.
+    pub fn explore<F>(&self, root: Schedule, run: F) -> Option<Schedule>
+    where
+        F: Fn(Schedule) -> Exploration + Sync,
+    {
+        let workers: Vec<Worker<Schedule>> = (0..self.num_workers).map(|_| Worker::new()).collect();
+        let stealers: Arc<Vec<Stealer<Schedule>>> = Arc::new(workers.iter().map(|w| w.stealer()).collect());
+        workers[0].push(root);
+
+        let shared = Shared {
+            outstanding: AtomicUsize::new(1),
+            executions: AtomicUsize::new(0),
+            failed: AtomicBool::new(false),
+            failure: Mutex::new(None),
+        };
+
+        thread::scope(|scope| {
+            for (index, worker) in workers.into_iter().enumerate() {
+                let stealers = Arc::clone(&stealers);
+                let shared = &shared;
+                let run = &run;
+                let config = self.clone();
+                scope.spawn(move || config.worker_loop(index, worker, &stealers, shared, run));
+            }
+        });
+
+        shared.failure.into_inner().unwrap()
+    }
+
+    /// The loop run by a single worker thread: take work locally or by stealing, run it, and push
+    /// any discovered branches back onto the local deque, until the search is globally quiescent.
+    fn worker_loop<F>(
+        &self,
+        index: usize,
+        worker: Worker<Schedule>,
+        stealers: &[Stealer<Schedule>],
+        shared: &Shared,
+        run: &F,
+    ) where
+        F: Fn(Schedule) -> Exploration + Sync,
+    {
+        loop {
+            if self.stop_on_first_failure && shared.failed.load(Ordering::Acquire) {
+                break;
+            }
+
+            let prefix = match take_work(&worker, stealers, index) {
+                Some(prefix) => prefix,
+                None => {
+                    // No work to pop or steal right now. Terminate only on true global quiescence
+                    // (`outstanding == 0`); otherwise a peer is still running a prefix that may yet
+                    // push branches onto the frontier, so park briefly and recheck.
+                    if shared.outstanding.load(Ordering::Acquire) == 0 {
+                        break;
+                    }
+                    thread::yield_now();
+                    continue;
+                }
+            };
+
+            if let Some(max) = self.max_executions {
+                if shared.executions.fetch_add(1, Ordering::SeqCst) >= max {
+                    // This prefix will not be run; account for it so the counter still reaches zero.
+                    shared.outstanding.fetch_sub(1, Ordering::SeqCst);
+                    break;
+                }
+            }
+
+            let Exploration { branches, failure } = run(prefix);
+            // Count the discovered branches before releasing this prefix, so `outstanding` never
+            // dips to zero while work remains to be done.
+            shared.outstanding.fetch_add(branches.len(), Ordering::SeqCst);
+            for branch in branches {
+                worker.push(branch);
+            }
+            if let Some(failing) = failure {
+                if !shared.failed.swap(true, Ordering::SeqCst) {
+                    *shared.failure.lock().unwrap() = Some(failing);
+                }
+            }
+            shared.outstanding.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Take the next prefix to run: pop from the local deque, otherwise steal from peers round-robin.
+fn take_work(worker: &Worker<Schedule>, stealers: &[Stealer<Schedule>], index: usize) -> Option<Schedule> {
+    if let Some(prefix) = worker.pop() {
+        return Some(prefix);
+    }
+    let n = stealers.len();
+    (1..n).find_map(|k| stealers[(index + k) % n].steal())
+}
+
+/// A front-end that runs an inner [`Scheduler`] with a fixed prefix of scheduling decisions
+/// replayed first, then hands control to the inner scheduler.
+///
+/// Each worker of a [`ParallelExplorer`] builds one of these per prefix it takes from the frontier:
+/// the prefix pins the common portion of the schedule (like a
+/// [`ReplayScheduler`](crate::scheduler::ReplayScheduler)), and the inner scheduler explores the
+/// remainder, discovering the branch points that are serialized back onto the frontier as
+/// [`Schedule`]s. The `explore` closure is where the two meet — see the
+/// `explorer_drives_parallel_scheduler_over_all_interleavings` test for a worked example — while the
+/// executor is responsible for replaying each [`Schedule`] and collecting the new branch prefixes.
+#[derive(Debug, Clone)]
+pub struct ParallelScheduler<S> {
+    inner: S,
+    prefix: Vec<TaskId>,
+    position: usize,
+}
+
+impl<S: Scheduler + Clone> ParallelScheduler<S> {
+    /// Create a front-end that simply delegates to `inner` with no replay prefix.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            prefix: vec![],
+            position: 0,
+        }
+    }
+
+    /// Create a front-end that first replays the task steps of `prefix`, then defers to `inner`.
+    pub fn with_prefix(inner: S, prefix: &Schedule) -> Self {
+        let prefix = prefix
+            .steps
+            .iter()
+            .filter_map(|step| match step {
+                ScheduleStep::Task(task) => Some(task.clone()),
+                ScheduleStep::Random => None,
+            })
+            .collect();
+        Self {
+            inner,
+            prefix,
+            position: 0,
+        }
+    }
+
+    /// The next task from the replay prefix, if one remains.
+    fn replay(&mut self) -> Option<TaskId> {
+        let task = self.prefix.get(self.position).cloned();
+        if task.is_some() {
+            self.position += 1;
+        }
+        task
+    }
+}
+
+impl<S: Scheduler + Clone> Scheduler for ParallelScheduler<S> {
+    fn new_execution(&mut self) -> Option<Schedule> {
+        self.position = 0;
+        self.inner.new_execution()
+    }
+
+    fn next_task(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.replay()
+            .or_else(|| self.inner.next_task(runnable_tasks, current_task, is_yielding))
+    }
+
+    fn next_task_with_lookahead(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        lookahead: &[Lookahead],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.replay().or_else(|| {
+            self.inner.next_task_with_lookahead(runnable_tasks, lookahead, current_task, is_yielding)
+        })
+    }
+
+    fn next_task_with_context(
+        &mut self,
+        runnable_tasks: &[TaskId],
+        lookahead: &[Lookahead],
+        trace: &[ScheduleRecord],
+        current_task: Option<TaskId>,
+        is_yielding: bool,
+    ) -> Option<TaskId> {
+        self.replay().or_else(|| {
+            self.inner
+                .next_task_with_context(runnable_tasks, lookahead, trace, current_task, is_yielding)
+        })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.inner.next_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tid(id: usize) -> TaskId {
+        TaskId::from(id)
+    }
+
+    #[test]
+    fn deque_is_lifo_for_owner_and_fifo_for_stealers() {
+        let worker: Worker<u64> = Worker::new();
+        let stealer = worker.stealer();
+        worker.push(1);
+        worker.push(2);
+        worker.push(3);
+        // Owner pops from the back.
+        assert_eq!(worker.pop(), Some(3));
+        // Stealers take from the front.
+        assert_eq!(stealer.steal(), Some(1));
+        assert_eq!(stealer.steal(), Some(2));
+        assert_eq!(stealer.steal(), None);
+        assert!(worker.is_empty());
+    }
+
+    #[test]
+    fn explores_every_node_of_a_full_tree() {
+        let depth = 4;
+        let visited = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&visited);
+        let explorer = ParallelExplorer::new(4).stop_on_first_failure(false);
+        let result = explorer.explore(Schedule::new(0), move |schedule| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            if schedule.len() < depth {
+                let mut left = schedule.clone();
+                left.push_task(tid(0));
+                let mut right = schedule.clone();
+                right.push_task(tid(1));
+                Exploration {
+                    branches: vec![left, right],
+                    failure: None,
+                }
+            } else {
+                Exploration::default()
+            }
+        });
+        assert!(result.is_none());
+        // A full binary tree of the given depth has 2^(depth+1) - 1 nodes.
+        assert_eq!(visited.load(Ordering::SeqCst), (1 << (depth + 1)) - 1);
+    }
+
+    #[test]
+    fn returns_first_discovered_failure() {
+        let explorer = ParallelExplorer::new(4);
+        let result = explorer.explore(Schedule::new(0), |schedule| {
+            if schedule.len() >= 3 {
+                Exploration {
+                    branches: vec![],
+                    failure: Some(schedule),
+                }
+            } else {
+                let mut left = schedule.clone();
+                left.push_task(tid(0));
+                let mut right = schedule.clone();
+                right.push_task(tid(1));
+                Exploration {
+                    branches: vec![left, right],
+                    failure: None,
+                }
+            }
+        });
+        assert!(result.is_some());
+        assert!(result.unwrap().len() >= 3);
+    }
+
+    #[derive(Debug, Clone)]
+    struct FirstRunnable {
+        exhausted: bool,
+    }
+
+    impl Scheduler for FirstRunnable {
+        fn new_execution(&mut self) -> Option<Schedule> {
+            if self.exhausted {
+                None
+            } else {
+                self.exhausted = true;
+                Some(Schedule::new(0))
+            }
+        }
+
+        fn next_task(
+            &mut self,
+            runnable_tasks: &[TaskId],
+            _current_task: Option<TaskId>,
+            _is_yielding: bool,
+        ) -> Option<TaskId> {
+            runnable_tasks.first().cloned()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            0
+        }
+    }
+
+    #[test]
+    fn prefix_steers_initial_decisions_then_defers_to_inner() {
+        let prefix = Schedule::new_from_task_ids(0, vec![1usize, 0usize]);
+        let mut scheduler = ParallelScheduler::with_prefix(FirstRunnable { exhausted: false }, &prefix);
+        scheduler.new_execution();
+
+        let runnable = vec![tid(0), tid(1)];
+        // The prefix overrides the inner scheduler (which would pick the first runnable task).
+        assert_eq!(scheduler.next_task(&runnable, None, false), Some(tid(1)));
+        assert_eq!(scheduler.next_task(&runnable, None, false), Some(tid(0)));
+        // Once the prefix is exhausted, control returns to the inner scheduler.
+        assert_eq!(scheduler.next_task(&runnable, None, false), Some(tid(0)));
+    }
+
+    #[test]
+    fn explorer_drives_parallel_scheduler_over_all_interleavings() {
+        // Model a program of three tasks that each run a single step. The explorer fans out over the
+        // frontier, and each prefix is realized through a `ParallelScheduler` exactly as a worker
+        // would, proving the front-end and the driver compose end to end.
+        let ntasks = 3;
+        let leaves = Arc::new(Mutex::new(Vec::<Vec<usize>>::new()));
+        let sink = Arc::clone(&leaves);
+        let explorer = ParallelExplorer::new(3).stop_on_first_failure(false);
+
+        explorer.explore(Schedule::new(0), move |prefix| {
+            // Replay the prefix through a `ParallelScheduler`, recording the order it pins, then
+            // resume the inner scheduler for the remaining runnable tasks.
+            let mut scheduler = ParallelScheduler::with_prefix(FirstRunnable { exhausted: false }, &prefix);
+            scheduler.new_execution();
+
+            let mut remaining: Vec<usize> = (0..ntasks).collect();
+            let mut order: Vec<usize> = vec![];
+            for _ in 0..prefix.len() {
+                let runnable: Vec<TaskId> = remaining.iter().cloned().map(tid).collect();
+                let chosen = scheduler.next_task(&runnable, None, false).unwrap();
+                let picked = remaining.iter().position(|&t| tid(t) == chosen).unwrap();
+                order.push(remaining.remove(picked));
+            }
+
+            if remaining.is_empty() {
+                sink.lock().unwrap().push(order);
+                Exploration::default()
+            } else {
+                // Branch on every task that could run next, serializing each choice as a new prefix.
+                let branches = remaining
+                    .iter()
+                    .map(|&t| {
+                        let mut branch = prefix.clone();
+                        branch.push_task(tid(t));
+                        branch
+                    })
+                    .collect();
+                Exploration { branches, failure: None }
+            }
+        });
+
+        let mut leaves = Arc::try_unwrap(leaves).unwrap().into_inner().unwrap();
+        // Every one of the 3! = 6 interleavings is discovered exactly once.
+        assert_eq!(leaves.len(), 6);
+        leaves.sort();
+        leaves.dedup();
+        assert_eq!(leaves.len(), 6);
+    }
+}
@@ -0,0 +1,41 @@
+//! A small deterministic PRNG shared by the exhaustive schedulers.
+
+/// A seedable xorshift64 generator used to supply `next_u64` values during exhaustive exploration,
+/// so that replaying the same schedule observes the same sequence of data values.
+#[derive(Debug)]
+pub(crate) struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    /// Create a generator seeded with `seed`.
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: Self::nonzero(seed) }
+    }
+
+    /// Reset the generator back to `seed`, so the next execution reproduces the same values.
+    pub(crate) fn reset(&mut self, seed: u64) {
+        self.state = Self::nonzero(seed);
+    }
+
+    /// Map a seed to a nonzero state. Xorshift has a fixed point at zero — seeding it with `0` would
+    /// make every `next_u64` return `0` forever — so `DporScheduler::new(0)` and friends would
+    /// silently lose all data randomness. Substitute a nonzero constant in that case.
+    fn nonzero(seed: u64) -> u64 {
+        if seed == 0 {
+            0x9e3779b97f4a7c15
+        } else {
+            seed
+        }
+    }
+
+    /// Return the next pseudo-random `u64`.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+}